@@ -1,5 +1,8 @@
 use rand::seq::SliceRandom;
 use rand::{Rng, RngCore};
+use rand_distr::Normal;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::iter::zip;
 use std::ops::Index;
 
@@ -30,6 +33,7 @@ impl CrossoverMethod for UniformCrossover {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Chromosome {
     genes: Vec<f32>,
 }
@@ -86,6 +90,22 @@ impl SelectionMethod for PropSelection {
     }
 }
 
+pub struct TournamentSelection {
+    pub size: usize,
+}
+
+impl SelectionMethod for TournamentSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        (0..self.size)
+            .map(|_| population.choose(rng).expect("empty population"))
+            .max_by(|a, b| a.fitness().total_cmp(&b.fitness()))
+            .expect("empty tournament")
+    }
+}
+
 pub trait SelectionMethod {
     fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
     where
@@ -115,11 +135,10 @@ impl GuassianMutation {
 
 impl MutationMethod for GuassianMutation {
     fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
-        let chromosome = child.iter_mut();
-        for gene in chromosome {
-            let sign = if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+        let normal = Normal::new(0.0, self.coeff).expect("invalid mutation coeff");
+        for gene in child.iter_mut() {
             if rng.gen_bool(self.chance as f64) {
-                *gene += sign * self.coeff * rng.gen::<f32>()
+                *gene += rng.sample(normal);
             }
         }
     }
@@ -147,18 +166,37 @@ where
         }
     }
 
-    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I>
+    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I], elitism: usize) -> Vec<I>
+    where
+        I: Individual + Clone,
+    {
+        let mut next = Vec::with_capacity(population.len());
+
+        if elitism > 0 {
+            let mut ranked: Vec<&I> = population.iter().collect();
+            ranked.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+            next.extend(ranked.into_iter().take(elitism).cloned());
+        }
+
+        while next.len() < population.len() {
+            let parent_a = self.selection_method.select(rng, population).chromosome();
+            let parent_b = self.selection_method.select(rng, population).chromosome();
+            let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+            self.mutation_method.mutate(rng, &mut child);
+            next.push(I::create(child));
+        }
+
+        next
+    }
+
+    /// Returns the fittest individual of the current generation, e.g. to
+    /// checkpoint its brain before evolving the population further.
+    pub fn champion<'a, I>(&self, population: &'a [I]) -> Option<&'a I>
     where
         I: Individual,
     {
-        (0..population.len())
-            .map(|_| {
-                let parent_a = self.selection_method.select(rng, population).chromosome();
-                let parent_b = self.selection_method.select(rng, population).chromosome();
-                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
-                self.mutation_method.mutate(rng, &mut child);
-                I::create(child)
-            })
-            .collect()
+        population
+            .iter()
+            .max_by(|a, b| a.fitness().total_cmp(&b.fitness()))
     }
 }
@@ -1,57 +1,115 @@
+use nalgebra::{DMatrix, DVector};
 use rand::Rng;
-use std::iter::zip;
+use rand_distr::StandardNormal;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ActivationFunc {
+    Sigmoid,
+    Tanh,
+    ReLU,
+}
+
+impl ActivationFunc {
+    fn apply(&self, x: f32) -> f32 {
+        match self {
+            ActivationFunc::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            ActivationFunc::Tanh => x.tanh(),
+            ActivationFunc::ReLU => x.max(0.0),
+        }
+    }
+}
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Network {
     layers: Vec<Layer>,
+    activation: ActivationFunc,
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 struct Layer {
-    neurons: Vec<Neuron>,
-}
-
-#[derive(Debug)]
-struct Neuron {
-    weights: Vec<f32>,
-    bias: f32,
+    // `next x (current + 1)`; the last column holds the per-neuron bias.
+    weights: DMatrix<f32>,
 }
 
 impl Network {
-    pub fn prop(&self, mut inputs: Vec<f32>) -> Vec<f32> {
+    pub fn prop(&self, inputs: Vec<f32>) -> Vec<f32> {
+        let mut inputs = DVector::from_vec(inputs);
         for layer in &self.layers {
-            inputs = layer.prop(inputs);
+            inputs = layer.prop(inputs, self.activation);
         }
-        inputs
+        inputs.iter().copied().collect()
     }
+
     pub fn new(topology: &[usize]) -> Self {
+        Self::new_with_activation(topology, ActivationFunc::ReLU)
+    }
+
+    pub fn new_with_activation(topology: &[usize], activation: ActivationFunc) -> Self {
         let layers = topology
             .windows(2)
             .map(|layers| Layer::random(layers[0], layers[1]))
             .collect();
-        Self { layers }
+        Self { layers, activation }
     }
-}
 
-impl Layer {
-    fn prop(&self, inputs: Vec<f32>) -> Vec<f32> {
-        self.neurons.iter().map(|n| n.prop(&inputs)).collect()
+    pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
+        self.layers.iter().flat_map(|layer| {
+            let w = &layer.weights;
+            (0..w.nrows()).flat_map(move |r| (0..w.ncols()).map(move |c| w[(r, c)]))
+        })
     }
-    fn random(current: usize, next: usize) -> Self {
-        let neurons = (0..next).map(|_| Neuron::random(current)).collect();
-        Self { neurons }
+
+    pub fn from_weights(topology: &[usize], weights: impl IntoIterator<Item = f32>) -> Self {
+        let mut weights = weights.into_iter();
+        let layers = topology
+            .windows(2)
+            .map(|layer| Layer::from_weights(layer[0], layer[1], &mut weights))
+            .collect();
+        assert!(weights.next().is_none(), "too many weights provided");
+        Self {
+            layers,
+            activation: ActivationFunc::ReLU,
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json<W: std::io::Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, self)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json<R: std::io::Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
     }
 }
 
-impl Neuron {
-    fn prop(&self, input: &[f32]) -> f32 {
-        let output = zip(input, &self.weights).map(|(a, b)| a * b).sum::<f32>();
-        (self.bias + output).max(0.0)
+impl Layer {
+    fn prop(&self, inputs: DVector<f32>, activation: ActivationFunc) -> DVector<f32> {
+        let biased = inputs.push(1.0);
+        (&self.weights * biased).map(|x| activation.apply(x))
     }
-    fn random(inputs: usize) -> Self {
+
+    fn random(current: usize, next: usize) -> Self {
+        // He-et-al. initialization: N(0, 1) scaled by sqrt(2 / fan_in).
         let mut rng = rand::thread_rng();
-        let bias: f32 = rng.gen_range(-1.0..=1.0);
-        let weights = (0..inputs).map(|_| rng.gen_range(-1.0..=1.0)).collect();
-        Self { bias, weights }
+        let scale = (2.0 / current as f32).sqrt();
+        let weights = DMatrix::from_fn(next, current + 1, |_, _| {
+            rng.sample::<f32, _>(StandardNormal) * scale
+        });
+        Self { weights }
+    }
+
+    fn from_weights(current: usize, next: usize, weights: &mut dyn Iterator<Item = f32>) -> Self {
+        let (rows, cols) = (next, current + 1);
+        let data: Vec<f32> = weights.by_ref().take(rows * cols).collect();
+        assert_eq!(data.len(), rows * cols, "not enough weights provided");
+        Self {
+            weights: DMatrix::from_row_iterator(rows, cols, data),
+        }
     }
 }
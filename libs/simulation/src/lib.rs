@@ -1,8 +1,28 @@
+use genetic_algorithm as ga;
+use genetic_algorithm::Individual;
 use nalgebra as na;
+use neural_network as nn;
 use rand::{Rng, RngCore};
+use std::f32::consts::PI;
+
+const SPEED_MIN: f32 = 0.001;
+const SPEED_MAX: f32 = 0.005;
+const SPEED_ACCEL: f32 = 0.2;
+const ROTATION_ACCEL: f32 = PI / 2.0;
+const FOOD_SIZE: f32 = 0.01;
+const GENERATION_LENGTH: usize = 2500;
 
 pub struct Simulation {
     world: World,
+    ga: ga::GeneticAlgorithm<ga::TournamentSelection>,
+    age: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Statistics {
+    pub min_fitness: f32,
+    pub max_fitness: f32,
+    pub avg_fitness: f32,
 }
 
 #[derive(Debug)]
@@ -14,6 +34,9 @@ pub struct World {
 pub struct Animal {
     position: na::Point2<f32>,
     velocity: na::Vector2<f32>,
+    eye: Eye,
+    brain: nn::Network,
+    satiation: usize,
 }
 
 #[derive(Debug)]
@@ -21,15 +44,103 @@ pub struct Food {
     position: na::Point2<f32>,
 }
 
+#[derive(Debug)]
+pub struct Eye {
+    fov_range: f32,
+    fov_angle: f32,
+    cells: usize,
+}
+
 impl Simulation {
     pub fn random(rng: &mut dyn RngCore) -> Self {
+        let ga = ga::GeneticAlgorithm::new(
+            ga::TournamentSelection { size: 3 },
+            ga::UniformCrossover,
+            ga::GuassianMutation::new(0.01, 0.3),
+        );
         Self {
             world: World::random(rng),
+            ga,
+            age: 0,
         }
     }
     pub fn world(&self) -> &World {
         &self.world
     }
+
+    pub fn step(&mut self, rng: &mut dyn RngCore) {
+        self.process_brains();
+        self.process_movement(rng);
+        self.age += 1;
+    }
+
+    /// Advances the simulation and, once a generation has elapsed, evolves the
+    /// population, returning that generation's fitness statistics.
+    pub fn train(&mut self, rng: &mut dyn RngCore) -> Option<Statistics> {
+        self.step(rng);
+        if self.age > GENERATION_LENGTH {
+            Some(self.evolve(rng))
+        } else {
+            None
+        }
+    }
+
+    fn process_movement(&mut self, rng: &mut dyn RngCore) {
+        for animal in &mut self.world.animals {
+            animal.position += animal.velocity;
+            animal.position.x = na::wrap(animal.position.x, 0.0, 1.0);
+            animal.position.y = na::wrap(animal.position.y, 0.0, 1.0);
+
+            for food in &mut self.world.foods {
+                if na::distance(&animal.position, &food.position) < FOOD_SIZE {
+                    animal.satiation += 1;
+                    food.position = rng.gen();
+                }
+            }
+        }
+    }
+
+    fn evolve(&mut self, rng: &mut dyn RngCore) -> Statistics {
+        self.age = 0;
+
+        let population: Vec<_> = self
+            .world
+            .animals
+            .iter()
+            .map(AnimalIndividual::from_animal)
+            .collect();
+
+        let stats = Statistics::from_population(&population);
+
+        let next = self.ga.evolve(rng, &population, 1);
+        self.world.animals = next
+            .into_iter()
+            .map(|individual| individual.into_animal(rng))
+            .collect();
+
+        for food in &mut self.world.foods {
+            food.position = rng.gen();
+        }
+
+        stats
+    }
+
+    fn process_brains(&mut self) {
+        for animal in &mut self.world.animals {
+            let vision =
+                animal
+                    .eye
+                    .process_vision(animal.position, animal.velocity, &self.world.foods);
+
+            let response = animal.brain.prop(vision);
+            let speed = response[0].clamp(-SPEED_ACCEL, SPEED_ACCEL);
+            let rotation = response[1].clamp(-ROTATION_ACCEL, ROTATION_ACCEL);
+
+            let speed = (animal.velocity.norm() + speed).clamp(SPEED_MIN, SPEED_MAX);
+            let heading = animal.velocity.y.atan2(animal.velocity.x) + rotation;
+            animal.velocity = na::Vector2::new(heading.cos(), heading.sin()) * speed;
+        }
+    }
 }
 
 impl World {
@@ -50,12 +161,36 @@ impl World {
 
 impl Animal {
     pub fn random(rng: &mut dyn RngCore) -> Self {
+        let eye = Eye::default();
+        let brain = nn::Network::new(&Self::topology(&eye));
+        Self::new(eye, brain, rng)
+    }
+
+    fn new(eye: Eye, brain: nn::Network, rng: &mut dyn RngCore) -> Self {
         Self {
             position: rng.gen(),
             velocity: rng.gen(),
+            eye,
+            brain,
+            satiation: 0,
         }
     }
 
+    fn topology(eye: &Eye) -> [usize; 3] {
+        [eye.cells(), eye.cells() * 2, 2]
+    }
+
+    /// Rebuilds an animal with a fresh position from an evolved chromosome.
+    fn from_chromosome(chromosome: ga::Chromosome, rng: &mut dyn RngCore) -> Self {
+        let eye = Eye::default();
+        let brain = nn::Network::from_weights(&Self::topology(&eye), chromosome);
+        Self::new(eye, brain, rng)
+    }
+
+    fn as_chromosome(&self) -> ga::Chromosome {
+        self.brain.weights().collect()
+    }
+
     pub fn pos(&self) -> na::Point2<f32> {
         self.position
     }
@@ -65,6 +200,67 @@ impl Animal {
     }
 }
 
+/// Bridges a living [`Animal`] and the genetic algorithm: its fitness is the
+/// food it ate, its chromosome the flattened weights of its brain.
+#[derive(Clone)]
+pub struct AnimalIndividual {
+    fitness: f32,
+    chromosome: ga::Chromosome,
+}
+
+impl AnimalIndividual {
+    fn from_animal(animal: &Animal) -> Self {
+        Self {
+            fitness: animal.satiation as f32,
+            chromosome: animal.as_chromosome(),
+        }
+    }
+
+    fn into_animal(self, rng: &mut dyn RngCore) -> Animal {
+        Animal::from_chromosome(self.chromosome, rng)
+    }
+}
+
+impl Statistics {
+    fn from_population(population: &[AnimalIndividual]) -> Self {
+        assert!(!population.is_empty());
+
+        let mut min = f32::MAX;
+        let mut max = f32::MIN;
+        let mut sum = 0.0;
+
+        for individual in population {
+            let fitness = individual.fitness();
+            min = min.min(fitness);
+            max = max.max(fitness);
+            sum += fitness;
+        }
+
+        Self {
+            min_fitness: min,
+            max_fitness: max,
+            avg_fitness: sum / population.len() as f32,
+        }
+    }
+}
+
+impl ga::Individual for AnimalIndividual {
+    fn fitness(&self) -> f32 {
+        self.fitness
+    }
+
+    fn chromosome(&self) -> &ga::Chromosome {
+        &self.chromosome
+    }
+
+    fn create(chromosome: ga::Chromosome) -> Self {
+        Self {
+            fitness: 0.0,
+            chromosome,
+        }
+    }
+}
+
 impl Food {
     pub fn random(rng: &mut dyn RngCore) -> Self {
         Self {
@@ -75,3 +271,52 @@ impl Food {
         self.position
     }
 }
+
+impl Eye {
+    pub fn new(fov_range: f32, fov_angle: f32, cells: usize) -> Self {
+        Self {
+            fov_range,
+            fov_angle,
+            cells,
+        }
+    }
+
+    pub fn cells(&self) -> usize {
+        self.cells
+    }
+
+    pub fn process_vision(
+        &self,
+        position: na::Point2<f32>,
+        velocity: na::Vector2<f32>,
+        foods: &[Food],
+    ) -> Vec<f32> {
+        let mut cells = vec![0.0; self.cells];
+        let heading = velocity.y.atan2(velocity.x);
+
+        for food in foods {
+            let vec = food.position - position;
+            let dist = vec.norm();
+            if dist > self.fov_range {
+                continue;
+            }
+
+            let angle = na::wrap(vec.y.atan2(vec.x) - heading, -PI, PI);
+            if angle < -self.fov_angle / 2.0 || angle > self.fov_angle / 2.0 {
+                continue;
+            }
+
+            let cell = (angle + self.fov_angle / 2.0) / self.fov_angle * self.cells as f32;
+            let cell = (cell as usize).min(self.cells - 1);
+            cells[cell] += (self.fov_range - dist) / self.fov_range;
+        }
+
+        cells
+    }
+}
+
+impl Default for Eye {
+    fn default() -> Self {
+        Self::new(0.25, PI / 4.0 * 3.0, 9)
+    }
+}